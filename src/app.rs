@@ -1,4 +1,7 @@
-use crate::scan::{compute_file_hashes, remove_files, ScanError, INFECTED_HASHES};
+use crate::orchestrator::{run_scan, run_scan_all, MatchKind, ScanHandles, ScanMatch};
+use crate::quarantine::{self, QuarantineEntry, QuarantineError};
+use crate::scan::ScanError;
+use crate::signatures::SignatureDatabase;
 use dashmap::DashMap;
 use egui::mutex::RwLock;
 use egui::{Color32, ProgressBar};
@@ -6,6 +9,8 @@ use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 use std::sync::Arc;
 
+pub const APP_ID: &str = "Modrinth Malware Scanner";
+
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct TemplateApp {
@@ -19,14 +24,36 @@ pub struct TemplateApp {
     #[serde(skip)]
     pub current_progress: Arc<AtomicI32>,
     #[serde(skip)]
-    pub scan_result: Arc<DashMap<String, PathBuf>>,
+    pub scan_result: Arc<DashMap<String, ScanMatch>>,
     #[serde(skip)]
     pub scan_status: Arc<AtomicBool>,
     #[serde(skip)]
     pub current_error: Arc<RwLock<Option<ScanError>>>,
+    #[serde(skip)]
+    pub stop_flag: Arc<AtomicBool>,
+    #[serde(skip)]
+    pub signature_db: Arc<RwLock<SignatureDatabase>>,
+    pub fuzzy_matching: bool,
+
+    #[serde(skip)]
+    pub quarantine_error: Arc<RwLock<Option<QuarantineError>>>,
+    #[serde(skip)]
+    pub quarantined: Vec<QuarantineEntry>,
+    #[serde(skip)]
+    pub quarantine_busy: Arc<AtomicBool>,
+    /// Refreshed quarantine listing from the most recent background quarantine/restore/
+    /// delete operation, picked up into `quarantined` on the next frame.
+    #[serde(skip)]
+    pub quarantined_update: Arc<RwLock<Option<Vec<QuarantineEntry>>>>,
+
+    /// Launchers detected on this machine, refreshed on startup and via the "Refresh"
+    /// button rather than every frame, since detecting them does a handful of
+    /// filesystem checks and `update` can run many times a second.
+    #[serde(skip)]
+    pub detected_launchers: Vec<(Launcher, PathBuf)>,
 }
 
-#[derive(PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum Launcher {
     Modrinth,
     Prism,
@@ -37,7 +64,7 @@ pub enum Launcher {
 }
 
 impl Launcher {
-    fn get_data_directory(&self) -> Option<PathBuf> {
+    pub(crate) fn get_data_directory(&self) -> Option<PathBuf> {
         match self {
             Launcher::Modrinth => {
                 dirs::config_dir().map(|x| x.join("com.modrinth.theseus").join("profiles"))
@@ -50,6 +77,35 @@ impl Launcher {
             Launcher::CustomDirectory => None,
         }
     }
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Launcher::Modrinth => "Modrinth App",
+            Launcher::Prism => "Prism Launcher",
+            Launcher::ATLauncher => "ATLauncher",
+            Launcher::Vanilla => "Vanilla",
+            Launcher::CustomDirectory => "Custom directory",
+        }
+    }
+
+    const KNOWN: [Launcher; 4] = [
+        Launcher::Modrinth,
+        Launcher::Prism,
+        Launcher::ATLauncher,
+        Launcher::Vanilla,
+    ];
+
+    /// Probes every known launcher's expected data directory and returns the ones that
+    /// actually exist on this machine, each paired with its directory.
+    pub(crate) fn detect_installed() -> Vec<(Launcher, PathBuf)> {
+        Self::KNOWN
+            .into_iter()
+            .filter_map(|launcher| {
+                let dir = launcher.get_data_directory()?;
+                dir.exists().then_some((launcher, dir))
+            })
+            .collect()
+    }
 }
 
 impl Default for TemplateApp {
@@ -63,17 +119,52 @@ impl Default for TemplateApp {
             scan_result: Arc::new(DashMap::new()),
             scan_status: Arc::new(AtomicBool::new(false)),
             current_error: Arc::new(RwLock::new(None)),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            signature_db: Arc::new(RwLock::new(SignatureDatabase::embedded())),
+            fuzzy_matching: false,
+            quarantine_error: Arc::new(RwLock::new(None)),
+            quarantined: Vec::new(),
+            quarantine_busy: Arc::new(AtomicBool::new(false)),
+            quarantined_update: Arc::new(RwLock::new(None)),
+            detected_launchers: Vec::new(),
         }
     }
 }
 
 impl TemplateApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let mut app: Self = if let Some(storage) = cc.storage {
+            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
+        } else {
+            Default::default()
+        };
+
+        let cache_path = crate::signatures::cache_path(APP_ID);
+
+        // Render immediately with whatever's on disk (or the embedded fallback); refresh
+        // from the network in the background so a slow/hung connection can't block startup.
+        *app.signature_db.write() = match &cache_path {
+            Some(cache_path) => SignatureDatabase::load_cached_or_embedded(cache_path),
+            None => SignatureDatabase::embedded(),
+        };
+
+        if let Some(cache_path) = cache_path {
+            let signature_db = app.signature_db.clone();
+            std::thread::spawn(move || {
+                if let Ok(fetched) = SignatureDatabase::fetch() {
+                    let _ = fetched.save_to_cache(&cache_path);
+                    *signature_db.write() = fetched;
+                }
+            });
         }
 
-        Default::default()
+        if let Some(quarantine_dir) = quarantine::quarantine_dir(APP_ID) {
+            app.quarantined = quarantine::list_quarantined(&quarantine_dir);
+        }
+
+        app.detected_launchers = Launcher::detect_installed();
+
+        app
     }
 }
 
@@ -85,6 +176,10 @@ impl eframe::App for TemplateApp {
 
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Some(updated) = self.quarantined_update.write().take() {
+            self.quarantined = updated;
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // The central panel the region left after adding TopPanel's and SidePanel's
             ui.heading("modrinth scanner");
@@ -101,6 +196,12 @@ impl eframe::App for TemplateApp {
             ui.add_space(10.0);
             ui.end_row();
 
+            ui.checkbox(
+                &mut self.fuzzy_matching,
+                "Also flag possible variants of known malware (fuzzy matching)",
+            );
+            ui.end_row();
+
             if self.launcher == Launcher::CustomDirectory {
                 ui.horizontal_wrapped(|ui| {
                     ui.label("Select directory: ");
@@ -127,20 +228,29 @@ impl eframe::App for TemplateApp {
                     if (!self.scanning && !self.scan_status.load(Ordering::Relaxed)) && ui.button("Begin scan").clicked() {
                         self.scanning = true;
                         self.scan_result = Arc::new(DashMap::new());
+                        self.stop_flag.store(false, Ordering::SeqCst);
 
-                        let current_progress = self.current_progress.clone();
-                        let total_count = self.total_count.clone();
+                        let handles = ScanHandles {
+                            scanned_file_count: self.current_progress.clone(),
+                            discovered_file_count: self.total_count.clone(),
+                            stop_flag: self.stop_flag.clone(),
+                        };
                         let scan_result = self.scan_result.clone();
                         let scan_status = self.scan_status.clone();
                         let current_error = self.current_error.clone();
+                        let signature_db = self.signature_db.read().clone();
+                        let fuzzy_matching = self.fuzzy_matching;
 
                         std::thread::spawn(move || {
-                            match compute_file_hashes(path.as_path(), current_progress, total_count) {
-                                Ok(res) => {
-                                    for (key, val) in res {
-                                        if INFECTED_HASHES.contains(&&*key) {
-                                            scan_result.insert(key, val);
-                                        }
+                            match run_scan(path.as_path(), &signature_db, fuzzy_matching, &handles)
+                            {
+                                Ok((res, report)) => {
+                                    for (key, scan_match) in res {
+                                        scan_result.insert(key, scan_match);
+                                    }
+                                    if let Some(error) = report.errors.first() {
+                                        *current_error.write() =
+                                            Some(ScanError::Aggregate(error.clone()));
                                     }
                                 }
                                 Err(err) => {
@@ -153,6 +263,65 @@ impl eframe::App for TemplateApp {
                 });
             }
 
+            if !self.scanning && !self.scan_status.load(Ordering::Relaxed) {
+                ui.horizontal_wrapped(|ui| {
+                    if ui.button("Refresh detected launchers").clicked() {
+                        self.detected_launchers = Launcher::detect_installed();
+                    }
+                });
+            }
+
+            let detected_launchers = self.detected_launchers.clone();
+            if !self.scanning
+                && !self.scan_status.load(Ordering::Relaxed)
+                && !detected_launchers.is_empty()
+            {
+                ui.horizontal_wrapped(|ui| {
+                    if ui
+                        .button(format!(
+                            "Scan all detected launchers ({})",
+                            detected_launchers.len()
+                        ))
+                        .clicked()
+                    {
+                        self.scanning = true;
+                        self.scan_result = Arc::new(DashMap::new());
+                        self.stop_flag.store(false, Ordering::SeqCst);
+
+                        let handles = ScanHandles {
+                            scanned_file_count: self.current_progress.clone(),
+                            discovered_file_count: self.total_count.clone(),
+                            stop_flag: self.stop_flag.clone(),
+                        };
+                        let scan_result = self.scan_result.clone();
+                        let scan_status = self.scan_status.clone();
+                        let current_error = self.current_error.clone();
+                        let signature_db = self.signature_db.read().clone();
+                        let fuzzy_matching = self.fuzzy_matching;
+                        let targets: Vec<(String, PathBuf)> = detected_launchers
+                            .iter()
+                            .map(|(launcher, dir)| (launcher.label().to_string(), dir.clone()))
+                            .collect();
+
+                        std::thread::spawn(move || {
+                            let (res, report) = run_scan_all(
+                                &targets,
+                                &signature_db,
+                                fuzzy_matching,
+                                &handles,
+                            );
+                            for (key, scan_match) in res {
+                                scan_result.insert(key, scan_match);
+                            }
+                            if let Some(error) = report.errors.first() {
+                                *current_error.write() = Some(ScanError::Aggregate(error.clone()));
+                            }
+                            scan_status.store(true, Ordering::SeqCst);
+                        });
+                    }
+                });
+            }
+
             if let Some(val) = self.current_error.read().as_ref() {
                 ui.add_space(10.0);
                 ui.colored_label(Color32::RED, format!("Error scanning: {}", val));
@@ -168,22 +337,57 @@ impl eframe::App for TemplateApp {
                 } else {
                     ui.colored_label(Color32::RED, "Malware found at paths below:");
                     ui.vertical(|ui| {
-                        for val in self.scan_result.iter() {
+                        for entry in self.scan_result.iter() {
                             ui.spacing_mut().item_spacing.y = 5.0;
-                            ui.label(val.value().display().to_string());
+                            let scan_match = entry.value();
+                            let prefix = match &scan_match.source {
+                                Some(source) => format!("[{source}] "),
+                                None => String::new(),
+                            };
+                            match scan_match.kind {
+                                MatchKind::Exact => {
+                                    ui.label(format!("{prefix}{}", scan_match.path.display()));
+                                }
+                                MatchKind::PossibleVariant(score) => {
+                                    ui.colored_label(
+                                        Color32::YELLOW,
+                                        format!(
+                                            "{prefix}{} (possible variant, {score}% match)",
+                                            scan_match.path.display()
+                                        ),
+                                    );
+                                }
+                            }
                         }
                     });
 
-                    if ui.button("Remove files").clicked() {
-                        let current_error = self.current_error.clone();
-                        let paths = self.scan_result.iter().map(|x| x.value().clone()).collect();
+                    if !self.quarantine_busy.load(Ordering::Relaxed)
+                        && ui.button("Quarantine files").clicked()
+                    {
+                        if let Some(quarantine_dir) = quarantine::quarantine_dir(APP_ID) {
+                            self.quarantine_busy.store(true, Ordering::SeqCst);
 
-                        std::thread::spawn(move || {
-                            if let Err(err) = remove_files(paths) {
-                               *current_error.write() = Some(err);
+                            let quarantine_error = self.quarantine_error.clone();
+                            let quarantined_update = self.quarantined_update.clone();
+                            let quarantine_busy = self.quarantine_busy.clone();
+                            let paths: Vec<PathBuf> = self
+                                .scan_result
+                                .iter()
+                                .map(|x| x.value().path.clone())
+                                .collect();
+
+                            std::thread::spawn(move || {
+                                if let Err(err) =
+                                    quarantine::quarantine_files(paths, &quarantine_dir)
+                                {
+                                    *quarantine_error.write() = Some(err);
                                 }
 
-                        });
+                                *quarantined_update.write() =
+                                    Some(quarantine::list_quarantined(&quarantine_dir));
+                                quarantine_busy.store(false, Ordering::SeqCst);
+                            });
+                        }
                     }
 
                     ui.horizontal_wrapped(|ui| {
@@ -209,11 +413,85 @@ impl eframe::App for TemplateApp {
                     ui.strong(text);
 
                     ui.add(ProgressBar::new(progress));
+
+                    if ui.button("Stop scan").clicked() {
+                        self.stop_flag.store(true, Ordering::SeqCst);
+                    }
+                });
+            }
+
+            if let Some(val) = self.quarantine_error.read().as_ref() {
+                ui.add_space(10.0);
+                ui.colored_label(Color32::RED, format!("Error managing quarantine: {}", val));
+            }
+
+            if !self.quarantined.is_empty() {
+                ui.add_space(10.0);
+                ui.heading("Quarantined files");
+
+                let quarantine_dir = quarantine::quarantine_dir(APP_ID);
+                let busy = self.quarantine_busy.load(Ordering::Relaxed);
+                let mut restore = None;
+                let mut delete = None;
+
+                ui.vertical(|ui| {
+                    for entry in &self.quarantined {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(entry.original_path.display().to_string());
+                            if !busy && ui.button("Restore").clicked() {
+                                restore = Some(entry.clone());
+                            }
+                            if !busy && ui.button("Delete permanently").clicked() {
+                                delete = Some(entry.clone());
+                            }
+                        });
+                    }
                 });
+
+                if let Some(quarantine_dir) = quarantine_dir {
+                    if let Some(entry) = restore {
+                        self.quarantine_busy.store(true, Ordering::SeqCst);
+
+                        let quarantine_error = self.quarantine_error.clone();
+                        let quarantined_update = self.quarantined_update.clone();
+                        let quarantine_busy = self.quarantine_busy.clone();
+
+                        std::thread::spawn(move || {
+                            if let Err(err) = quarantine::restore_files(&[entry], &quarantine_dir) {
+                                *quarantine_error.write() = Some(err);
+                            }
+                            *quarantined_update.write() =
+                                Some(quarantine::list_quarantined(&quarantine_dir));
+                            quarantine_busy.store(false, Ordering::SeqCst);
+                        });
+                    }
+                    if let Some(entry) = delete {
+                        self.quarantine_busy.store(true, Ordering::SeqCst);
+
+                        let quarantine_error = self.quarantine_error.clone();
+                        let quarantined_update = self.quarantined_update.clone();
+                        let quarantine_busy = self.quarantine_busy.clone();
+
+                        std::thread::spawn(move || {
+                            if let Err(err) =
+                                quarantine::delete_quarantined(&[entry], &quarantine_dir)
+                            {
+                                *quarantine_error.write() = Some(err);
+                            }
+                            *quarantined_update.write() =
+                                Some(quarantine::list_quarantined(&quarantine_dir));
+                            quarantine_busy.store(false, Ordering::SeqCst);
+                        });
+                    }
+                }
             }
 
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 copyright_and_source(ui);
+                ui.label(format!(
+                    "Signature database: {}",
+                    self.signature_db.read().version
+                ));
                 egui::widgets::global_dark_light_mode_buttons(ui);
             });
         });
@@ -0,0 +1,143 @@
+use crate::app::{Launcher, APP_ID};
+use crate::orchestrator::{run_scan, ScanHandles};
+use crate::signatures::SignatureDatabase;
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "oracle", about = "Modrinth malware scanner")]
+pub enum Cli {
+    /// Scan a launcher or custom directory for known malware.
+    Scan(ScanArgs),
+}
+
+#[derive(clap::Args)]
+pub struct ScanArgs {
+    /// Custom directory to scan, instead of a launcher's data directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Launcher whose data directory to scan when `--path` isn't given.
+    #[arg(long, value_enum, default_value_t = LauncherArg::Modrinth)]
+    pub launcher: LauncherArg,
+
+    /// Also flag possible variants of known malware via fuzzy hashing.
+    #[arg(long)]
+    pub fuzzy: bool,
+
+    /// Emit a machine-readable report to stdout as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Clone, clap::ValueEnum)]
+pub enum LauncherArg {
+    Modrinth,
+    Prism,
+    Atlauncher,
+    Vanilla,
+}
+
+impl std::fmt::Display for LauncherArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LauncherArg::Modrinth => write!(f, "modrinth"),
+            LauncherArg::Prism => write!(f, "prism"),
+            LauncherArg::Atlauncher => write!(f, "atlauncher"),
+            LauncherArg::Vanilla => write!(f, "vanilla"),
+        }
+    }
+}
+
+impl From<LauncherArg> for Launcher {
+    fn from(value: LauncherArg) -> Self {
+        match value {
+            LauncherArg::Modrinth => Launcher::Modrinth,
+            LauncherArg::Prism => Launcher::Prism,
+            LauncherArg::Atlauncher => Launcher::ATLauncher,
+            LauncherArg::Vanilla => Launcher::Vanilla,
+        }
+    }
+}
+
+/// Runs the headless scan described by `cli` and returns the process exit code: `0` if
+/// the scan completed with no matches, `1` if matches were found or it failed.
+pub fn run(cli: Cli) -> i32 {
+    let Cli::Scan(args) = cli;
+
+    let dir = match args
+        .path
+        .clone()
+        .or_else(|| Launcher::from(args.launcher.clone()).get_data_directory())
+    {
+        Some(dir) => dir,
+        None => {
+            eprintln!("error: could not resolve a directory to scan; pass --path");
+            return 1;
+        }
+    };
+
+    eprintln!("Scanning {}...", dir.display());
+
+    let signature_db = match crate::signatures::cache_path(APP_ID) {
+        Some(cache_path) => SignatureDatabase::load_or_fetch(&cache_path),
+        None => SignatureDatabase::embedded(),
+    };
+
+    let handles = ScanHandles::default();
+
+    let progress_scanned = handles.scanned_file_count.clone();
+    let progress_discovered = handles.discovered_file_count.clone();
+    let progress_done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let progress_thread = {
+        let progress_done = progress_done.clone();
+        std::thread::spawn(move || {
+            while !progress_done.load(Ordering::Relaxed) {
+                let scanned = progress_scanned.load(Ordering::Relaxed);
+                let discovered = progress_discovered.load(Ordering::Relaxed);
+                eprint!("\rScanned {scanned}/{discovered} files");
+                std::thread::sleep(Duration::from_millis(250));
+            }
+        })
+    };
+
+    let result = run_scan(&dir, &signature_db, args.fuzzy, &handles);
+    progress_done.store(true, Ordering::Relaxed);
+    let _ = progress_thread.join();
+    eprintln!();
+
+    match result {
+        Ok((_, report)) => {
+            let found_malware = !report.matches.is_empty();
+
+            if args.json {
+                match serde_json::to_string_pretty(&report) {
+                    Ok(json) => println!("{json}"),
+                    Err(err) => {
+                        eprintln!("error: failed to serialize report: {err}");
+                        return 1;
+                    }
+                }
+            } else {
+                eprintln!(
+                    "Scanned {} of {} discovered files, found {} match(es)",
+                    report.scanned,
+                    report.discovered,
+                    report.matches.len()
+                );
+                for entry in &report.matches {
+                    eprintln!(" - {} ({})", entry.path.display(), entry.kind);
+                }
+            }
+
+            i32::from(found_malware)
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            1
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Context-triggered piecewise ("fuzzy") hashing, ssdeep-style. Unlike a SHA1, a fuzzy
+//! signature tolerates small byte-level changes, so it can flag a file as a likely
+//! variant of known malware even when a single byte has been patched to dodge an
+//! exact-hash check.
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const MIN_BLOCK_SIZE: u64 = 3;
+
+const FNV_OFFSET_BASIS: u32 = 0x811c9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// Score (0-100) at or above which a file is flagged as a "possible variant".
+pub const DEFAULT_THRESHOLD: u32 = 70;
+
+/// Compares two fuzzy signatures and returns a similarity score from 0 (unrelated) to
+/// 100 (identical), based on normalized edit distance between their piecewise hashes.
+pub fn compare(a: &str, b: &str) -> u32 {
+    let (a_block, a_sig) = split_signature(a);
+    let (b_block, b_sig) = split_signature(b);
+
+    if a_block == 0 || a_block != b_block || a_sig.is_empty() || b_sig.is_empty() {
+        return 0;
+    }
+
+    let distance = levenshtein(a_sig, b_sig);
+    let max_len = a_sig.chars().count().max(b_sig.chars().count());
+
+    100u32.saturating_sub((distance * 100 / max_len) as u32)
+}
+
+fn initial_block_size(len: u64) -> u64 {
+    let mut block_size = MIN_BLOCK_SIZE;
+    while block_size * 64 < len.max(1) {
+        block_size *= 2;
+    }
+    block_size
+}
+
+fn split_signature(signature: &str) -> (u64, &str) {
+    match signature.split_once(':') {
+        Some((block, rest)) => (block.parse().unwrap_or(0), rest),
+        None => (0, signature),
+    }
+}
+
+/// Rolling sum over a trailing 7-byte window, updated in O(1) per byte.
+struct RollingWindow {
+    buf: [u32; 7],
+    pos: usize,
+    sum: u32,
+}
+
+impl RollingWindow {
+    fn new() -> Self {
+        Self {
+            buf: [0; 7],
+            pos: 0,
+            sum: 0,
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> u32 {
+        self.sum = self.sum.wrapping_sub(self.buf[self.pos]);
+        self.sum = self.sum.wrapping_add(byte as u32);
+        self.buf[self.pos] = byte as u32;
+        self.pos = (self.pos + 1) % self.buf.len();
+        self.sum
+    }
+}
+
+/// Streaming piecewise hasher: callers feed bytes through [`update`](Self::update) in
+/// fixed-size chunks, the same way whole-file SHA1 hashing streams its buffer, instead
+/// of reading the whole file up front. The piecewise block size depends on the total
+/// input length, so that has to be known before the first byte arrives.
+pub struct FuzzyHasher {
+    block_size: u64,
+    window: RollingWindow,
+    block_hash: u32,
+    block_has_pending_byte: bool,
+    signature: String,
+}
+
+impl FuzzyHasher {
+    pub fn new(total_len: u64) -> Self {
+        Self {
+            block_size: initial_block_size(total_len),
+            window: RollingWindow::new(),
+            block_hash: FNV_OFFSET_BASIS,
+            block_has_pending_byte: false,
+            signature: String::new(),
+        }
+    }
+
+    /// Feeds the next chunk of the file, in order.
+    pub fn update(&mut self, chunk: &[u8]) {
+        for &byte in chunk {
+            let rolling = self.window.push(byte) as u64;
+            self.block_hash ^= byte as u32;
+            self.block_hash = self.block_hash.wrapping_mul(FNV_PRIME);
+            self.block_has_pending_byte = true;
+
+            if rolling % self.block_size == self.block_size - 1 {
+                self.signature
+                    .push(BASE64_ALPHABET[(self.block_hash & 0x3f) as usize] as char);
+                self.block_hash = FNV_OFFSET_BASIS;
+                self.block_has_pending_byte = false;
+            }
+        }
+    }
+
+    /// Finishes the signature, formatted as `<block_size>:<signature>`.
+    pub fn finish(self) -> String {
+        let mut signature = self.signature;
+        if self.block_has_pending_byte {
+            signature.push(BASE64_ALPHABET[(self.block_hash & 0x3f) as usize] as char);
+        }
+        format!("{}:{signature}", self.block_size)
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut prev = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cur = row[j];
+            row[j] = if a[i - 1] == b[j - 1] {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j - 1])
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_all(data: &[u8]) -> String {
+        let mut hasher = FuzzyHasher::new(data.len() as u64);
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    #[test]
+    fn compare_scores_identical_signatures_as_100() {
+        let data: Vec<u8> = (0..512).map(|i| (i % 251) as u8).collect();
+        let signature = hash_all(&data);
+
+        assert_eq!(compare(&signature, &signature), 100);
+    }
+
+    #[test]
+    fn compare_flags_a_single_byte_patch_as_a_variant() {
+        let mut data: Vec<u8> = (0..512).map(|i| (i % 251) as u8).collect();
+        let original = hash_all(&data);
+
+        data[256] ^= 0xff;
+        let patched = hash_all(&data);
+
+        assert!(
+            compare(&original, &patched) >= DEFAULT_THRESHOLD,
+            "a single patched byte shouldn't drop similarity below the variant threshold"
+        );
+    }
+
+    #[test]
+    fn compare_scores_unrelated_data_below_threshold() {
+        let a = hash_all(&(0..512).map(|i| (i % 251) as u8).collect::<Vec<u8>>());
+        let b = hash_all(
+            &(0..512)
+                .map(|i| ((i * 37 + 11) % 251) as u8)
+                .collect::<Vec<u8>>(),
+        );
+
+        assert!(compare(&a, &b) < DEFAULT_THRESHOLD);
+    }
+
+    #[test]
+    fn compare_rejects_mismatched_block_sizes() {
+        let small = hash_all(&vec![0u8; 32]);
+        let large = hash_all(&vec![0u8; 4096]);
+
+        assert_eq!(compare(&small, &large), 0);
+    }
+
+    #[test]
+    fn update_agrees_with_itself_across_chunk_sizes() {
+        let data: Vec<u8> = (0..2000).map(|i| ((i * 7 + 3) % 256) as u8).collect();
+        let whole = hash_all(&data);
+
+        let mut chunked = FuzzyHasher::new(data.len() as u64);
+        for chunk in data.chunks(17) {
+            chunked.update(chunk);
+        }
+
+        assert_eq!(whole, chunked.finish());
+    }
+}
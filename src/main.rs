@@ -1,9 +1,20 @@
 mod app;
+mod cli;
+mod fuzzy;
+mod orchestrator;
+mod quarantine;
 mod scan;
+mod signatures;
 
 fn main() -> eframe::Result<()> {
     env_logger::init();
 
+    // Any arguments mean we're being driven from a script or terminal; run headless.
+    if std::env::args().len() > 1 {
+        use clap::Parser;
+        std::process::exit(cli::run(cli::Cli::parse()));
+    }
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([400.0, 300.0])
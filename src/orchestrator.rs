@@ -0,0 +1,189 @@
+use crate::fuzzy;
+use crate::scan::{compute_file_hashes, ScanError};
+use crate::signatures::SignatureDatabase;
+use dashmap::DashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::Arc;
+
+/// A single flagged file: either an exact signature-database hit, or a fuzzy-hash
+/// match to a known-bad variant, scored 0-100 by [`fuzzy::compare`].
+#[derive(Clone)]
+pub enum MatchKind {
+    Exact,
+    PossibleVariant(u32),
+}
+
+#[derive(Clone)]
+pub struct ScanMatch {
+    pub path: PathBuf,
+    pub kind: MatchKind,
+    /// Which launcher/profile this match came from, when scanning several at once.
+    pub source: Option<String>,
+}
+
+/// The counters and cancellation flag a scan is driven by, shared between the caller
+/// (GUI or CLI) and the worker doing the hashing.
+pub struct ScanHandles {
+    pub scanned_file_count: Arc<AtomicI32>,
+    pub discovered_file_count: Arc<AtomicI32>,
+    pub stop_flag: Arc<AtomicBool>,
+}
+
+impl Default for ScanHandles {
+    fn default() -> Self {
+        Self {
+            scanned_file_count: Arc::new(AtomicI32::new(0)),
+            discovered_file_count: Arc::new(AtomicI32::new(0)),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct ScanReportEntry {
+    pub path: PathBuf,
+    pub hash: String,
+    pub kind: String,
+    pub score: Option<u32>,
+    pub source: Option<String>,
+}
+
+/// A machine-readable summary of a completed scan, suitable for the CLI's `--json` report.
+#[derive(serde::Serialize)]
+pub struct ScanReport {
+    pub discovered: i32,
+    pub scanned: i32,
+    pub matches: Vec<ScanReportEntry>,
+    pub errors: Vec<String>,
+}
+
+/// Hashes every file under `dir` (including jar/zip entries) and matches the results
+/// against `signature_db`, optionally with fuzzy variant matching. Shared by the GUI
+/// scan thread and the headless CLI so both stay in sync.
+pub fn run_scan(
+    dir: &Path,
+    signature_db: &SignatureDatabase,
+    fuzzy_matching: bool,
+    handles: &ScanHandles,
+) -> Result<(DashMap<String, ScanMatch>, ScanReport), ScanError> {
+    let (res, errors) = compute_file_hashes(
+        dir,
+        fuzzy_matching,
+        handles.scanned_file_count.clone(),
+        handles.discovered_file_count.clone(),
+        handles.stop_flag.clone(),
+    )?;
+
+    let scan_result = DashMap::new();
+    let mut entries = Vec::new();
+
+    for (hash, hashed_file) in res {
+        let scan_match = if signature_db.hashes.iter().any(|h| h == &hash) {
+            Some(ScanMatch {
+                path: hashed_file.path.clone(),
+                kind: MatchKind::Exact,
+                source: None,
+            })
+        } else if fuzzy_matching {
+            hashed_file
+                .fuzzy_signature
+                .as_deref()
+                .and_then(|signature| best_fuzzy_match(signature, &signature_db.fuzzy_hashes))
+                .map(|score| ScanMatch {
+                    path: hashed_file.path.clone(),
+                    kind: MatchKind::PossibleVariant(score),
+                    source: None,
+                })
+        } else {
+            None
+        };
+
+        if let Some(scan_match) = scan_match {
+            let (kind, score) = match scan_match.kind {
+                MatchKind::Exact => ("exact".to_string(), None),
+                MatchKind::PossibleVariant(score) => ("possible_variant".to_string(), Some(score)),
+            };
+
+            entries.push(ScanReportEntry {
+                path: scan_match.path.clone(),
+                hash: hash.clone(),
+                kind,
+                score,
+                source: scan_match.source.clone(),
+            });
+            scan_result.insert(hash, scan_match);
+        }
+    }
+
+    let report = ScanReport {
+        discovered: handles.discovered_file_count.load(Ordering::Relaxed),
+        scanned: handles.scanned_file_count.load(Ordering::Relaxed),
+        matches: entries,
+        errors,
+    };
+
+    Ok((scan_result, report))
+}
+
+/// Runs [`run_scan`] against each `(label, dir)` pair in turn, tagging every match with
+/// which launcher it came from and merging the results into one combined set. All
+/// targets share `handles`, so discovered/scanned counts accumulate across the whole
+/// pass and the stop flag cancels the remaining launchers too.
+pub fn run_scan_all(
+    targets: &[(String, PathBuf)],
+    signature_db: &SignatureDatabase,
+    fuzzy_matching: bool,
+    handles: &ScanHandles,
+) -> (DashMap<String, ScanMatch>, ScanReport) {
+    let combined = DashMap::new();
+    let mut matches = Vec::new();
+    let mut errors = Vec::new();
+
+    for (label, dir) in targets {
+        if handles.stop_flag.load(Ordering::Relaxed) {
+            break;
+        }
+
+        match run_scan(dir, signature_db, fuzzy_matching, handles) {
+            Ok((res, report)) => {
+                for (hash, mut scan_match) in res {
+                    scan_match.source = Some(label.clone());
+                    combined.insert(format!("{label}:{hash}"), scan_match);
+                }
+                matches.extend(report.matches.into_iter().map(|mut entry| {
+                    entry.source = Some(label.clone());
+                    entry
+                }));
+                errors.extend(
+                    report
+                        .errors
+                        .into_iter()
+                        .map(|err| format!("{label}: {err}")),
+                );
+            }
+            Err(err) => errors.push(format!("{label}: {err}")),
+        }
+    }
+
+    let report = ScanReport {
+        discovered: handles.discovered_file_count.load(Ordering::Relaxed),
+        scanned: handles.scanned_file_count.load(Ordering::Relaxed),
+        matches,
+        errors,
+    };
+
+    (combined, report)
+}
+
+/// Best similarity score for `signature` against `known_bad`, if it meets
+/// [`fuzzy::DEFAULT_THRESHOLD`].
+fn best_fuzzy_match(signature: &str, known_bad: &[String]) -> Option<u32> {
+    let best = known_bad
+        .iter()
+        .map(|known| fuzzy::compare(signature, known))
+        .max()
+        .unwrap_or(0);
+
+    (best >= fuzzy::DEFAULT_THRESHOLD).then_some(best)
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Error, Debug)]
+pub enum QuarantineError {
+    #[error("IO Error: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Error reading quarantine manifest: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// A file that's been moved into quarantine, along with enough metadata to restore it
+/// to its original location.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct QuarantineEntry {
+    pub original_path: PathBuf,
+    pub quarantined_name: String,
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+pub struct QuarantineManifest {
+    pub entries: Vec<QuarantineEntry>,
+}
+
+impl QuarantineManifest {
+    fn load(quarantine_dir: &Path) -> Self {
+        fs::read_to_string(quarantine_dir.join(MANIFEST_FILE_NAME))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, quarantine_dir: &Path) -> Result<(), QuarantineError> {
+        fs::write(
+            quarantine_dir.join(MANIFEST_FILE_NAME),
+            serde_json::to_vec(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Directory quarantined files and their manifest are stored under, next to the eframe
+/// app state so it survives restarts.
+pub fn quarantine_dir(app_id: &str) -> Option<PathBuf> {
+    eframe::storage_dir(app_id).map(|dir| dir.join("quarantine"))
+}
+
+/// Moves each flagged file into `quarantine_dir`, recording its original location in the
+/// manifest so it can be restored later. Files that are already gone are skipped. The
+/// manifest is persisted after every successful move, so a failure partway through
+/// (e.g. a later file is locked) never leaves an already-quarantined file untracked.
+/// Returns the first error encountered, if any, after quarantining everything it could.
+pub fn quarantine_files(
+    paths: Vec<PathBuf>,
+    quarantine_dir: &Path,
+) -> Result<(), QuarantineError> {
+    fs::create_dir_all(quarantine_dir)?;
+    let mut manifest = QuarantineManifest::load(quarantine_dir);
+    let mut first_error = None;
+
+    for original_path in paths {
+        if !original_path.exists() {
+            continue;
+        }
+
+        let quarantined_name =
+            format!("{}_{}", manifest.entries.len(), unique_suffix(&original_path));
+        let quarantined_path = quarantine_dir.join(&quarantined_name);
+
+        match move_file(&original_path, &quarantined_path) {
+            Ok(()) => {
+                manifest.entries.push(QuarantineEntry {
+                    original_path,
+                    quarantined_name,
+                });
+                if let Err(err) = manifest.save(quarantine_dir) {
+                    first_error.get_or_insert(err);
+                }
+            }
+            Err(err) => {
+                first_error.get_or_insert(QuarantineError::from(err));
+            }
+        }
+    }
+
+    match first_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Moves quarantined files back to their original locations and removes them from the
+/// manifest. The manifest is persisted after every successful restore; see
+/// [`quarantine_files`] for why. Returns the first error encountered, if any.
+pub fn restore_files(
+    entries: &[QuarantineEntry],
+    quarantine_dir: &Path,
+) -> Result<(), QuarantineError> {
+    let mut manifest = QuarantineManifest::load(quarantine_dir);
+    let mut first_error = None;
+
+    for entry in entries {
+        let quarantined_path = quarantine_dir.join(&entry.quarantined_name);
+        let restored = if quarantined_path.exists() {
+            (|| -> std::io::Result<()> {
+                if let Some(parent) = entry.original_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                move_file(&quarantined_path, &entry.original_path)
+            })()
+        } else {
+            Ok(())
+        };
+
+        match restored {
+            Ok(()) => {
+                manifest
+                    .entries
+                    .retain(|e| e.quarantined_name != entry.quarantined_name);
+                manifest.save(quarantine_dir)?;
+            }
+            Err(err) => {
+                first_error.get_or_insert(QuarantineError::from(err));
+            }
+        }
+    }
+
+    match first_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Permanently deletes quarantined files and removes them from the manifest. The
+/// manifest is persisted after every successful delete; see [`quarantine_files`] for
+/// why. Returns the first error encountered, if any.
+pub fn delete_quarantined(
+    entries: &[QuarantineEntry],
+    quarantine_dir: &Path,
+) -> Result<(), QuarantineError> {
+    let mut manifest = QuarantineManifest::load(quarantine_dir);
+    let mut first_error = None;
+
+    for entry in entries {
+        let quarantined_path = quarantine_dir.join(&entry.quarantined_name);
+        let deleted = if quarantined_path.exists() {
+            fs::remove_file(quarantined_path)
+        } else {
+            Ok(())
+        };
+
+        match deleted {
+            Ok(()) => {
+                manifest
+                    .entries
+                    .retain(|e| e.quarantined_name != entry.quarantined_name);
+                manifest.save(quarantine_dir)?;
+            }
+            Err(err) => {
+                first_error.get_or_insert(QuarantineError::from(err));
+            }
+        }
+    }
+
+    match first_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Moves a file, falling back to copy-then-delete when `rename` fails (e.g. the source
+/// and destination are on different filesystems, which `rename` can't handle).
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    fs::copy(from, to)?;
+    fs::remove_file(from)?;
+    Ok(())
+}
+
+/// Current quarantine manifest entries, for display/restore UI.
+pub fn list_quarantined(quarantine_dir: &Path) -> Vec<QuarantineEntry> {
+    QuarantineManifest::load(quarantine_dir).entries
+}
+
+fn unique_suffix(path: &Path) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut hasher);
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{:x}_{file_name}", hasher.finish())
+}
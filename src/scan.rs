@@ -1,20 +1,21 @@
+use crate::fuzzy::FuzzyHasher;
 use dashmap::DashMap;
+use rayon::iter::{ParallelBridge, ParallelIterator};
 use sha1::{Digest, Sha1};
 use std::fs::File;
-use std::io::Read;
+use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicI32, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 use walkdir::WalkDir;
 
-pub const INFECTED_HASHES: &[&str] = &[
-    "179b5da318604f97616b5108f305e2a8e4609484",
-    "1a1c4dcae846866c58cc1abf71fb7f7aa4e7352a",
-    "e4d55310039b965fce6756da5286b481cfb09946",
-    "2f47e57a6bedc729359ffaf6f0149876008b5cc3",
-    "2f47e57a6bedc729359ffaf6f0149876008b5cc3",
-];
+/// Maximum nesting depth we'll follow for archives-within-archives (e.g. a jar inside a jar).
+const MAX_ARCHIVE_DEPTH: usize = 4;
+
+/// Maximum total decompressed bytes we'll hash out of a single outer file's archive tree,
+/// to guard against zip-bomb style blowups.
+const MAX_DECOMPRESSED_BYTES: u64 = 1024 * 1024 * 1024;
 
 #[derive(Error, Debug)]
 pub enum ScanError {
@@ -22,42 +23,206 @@ pub enum ScanError {
     IO(#[from] std::io::Error),
     #[error("WalkDir Error: {0}")]
     WalkDir(#[from] walkdir::Error),
-    #[error("Error joining tasks")]
-    JoinError,
+    #[error("Error reading archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Archive exceeded the decompressed size limit")]
+    ArchiveTooLarge,
+    #[error("{0}")]
+    Aggregate(String),
+}
+
+/// A file discovered and hashed during a scan.
+pub struct HashedFile {
+    pub path: PathBuf,
+    /// Fuzzy signature, computed in the same read pass as the SHA1 hash. `None` for
+    /// archive entries and `.jar`/`.zip` files themselves (which aren't fuzzy-matched)
+    /// and whenever fuzzy matching is turned off for the scan.
+    pub fuzzy_signature: Option<String>,
 }
 
+/// Hashes every file under `dir`, returning the per-file results keyed by SHA1 hash
+/// alongside a human-readable message for each file that couldn't be hashed, so a
+/// per-file failure doesn't silently disappear from the scan.
 pub fn compute_file_hashes(
     dir: &Path,
+    fuzzy_matching: bool,
     scanned_file_count: Arc<AtomicI32>,
     discovered_file_count: Arc<AtomicI32>,
-) -> Result<DashMap<String, PathBuf>, ScanError> {
-    let hashes = Arc::new(DashMap::new());
-    let mut handles = vec![];
+    stop_flag: Arc<AtomicBool>,
+) -> Result<(DashMap<String, HashedFile>, Vec<String>), ScanError> {
+    let hashes = DashMap::new();
+    let errors = Mutex::new(Vec::new());
 
-    for entry in WalkDir::new(dir).follow_links(true) {
-        let entry = entry?;
+    WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .par_bridge()
+        .try_for_each(|entry| -> Result<(), ScanError> {
+            if stop_flag.load(Ordering::Relaxed) {
+                return Ok(());
+            }
 
-        if entry.path().is_file() {
-            discovered_file_count.fetch_add(1, Ordering::Relaxed);
+            let entry = entry?;
 
-            let hashes_clone = hashes.clone();
-            let scanned_file_count = scanned_file_count.clone();
-            let handle = std::thread::spawn(move || {
-                if let Ok(hash) = compute_file_sha1(entry.path()) {
-                    hashes_clone.insert(hash, entry.path().to_path_buf());
+            if entry.path().is_file() {
+                discovered_file_count.fetch_add(1, Ordering::Relaxed);
 
-                    scanned_file_count.fetch_add(1, Ordering::Relaxed);
+                let (file_hashes, result) =
+                    compute_file_hashes_for_path(entry.path(), fuzzy_matching);
+
+                for (hash, path, fuzzy_signature) in file_hashes {
+                    hashes.insert(hash, HashedFile { path, fuzzy_signature });
                 }
-            });
-            handles.push(handle);
-        }
+
+                match result {
+                    Ok(()) => {
+                        scanned_file_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(err) => errors
+                        .lock()
+                        .unwrap()
+                        .push(format!("{}: {err}", entry.path().display())),
+                }
+            }
+
+            Ok(())
+        })?;
+
+    Ok((hashes, errors.into_inner().unwrap()))
+}
+
+/// Hashes a single file discovered on disk. Archive files are hashed entry-by-entry
+/// (see [`hash_archive_entries`]); everything else is hashed as a whole, streaming its
+/// bytes once through both the SHA1 and (when enabled) fuzzy hashers rather than reading
+/// the file twice.
+///
+/// Returns whatever entries were successfully hashed alongside the outcome, rather than
+/// an all-or-nothing `Result`, so a failure partway through an archive (e.g. a
+/// decompressed-size budget trip) doesn't throw away entries — possibly including known-
+/// bad matches — hashed earlier in the same file. If a `.jar`/`.zip` file turns out not
+/// to be a readable archive at all (a corrupted/truncated download, or a non-zip file
+/// renamed to `.jar`), falls back to a whole-file SHA1 so it still gets scanned instead
+/// of silently dropping out of the results.
+fn compute_file_hashes_for_path(
+    file_path: &Path,
+    fuzzy_matching: bool,
+) -> (Vec<(String, PathBuf, Option<String>)>, Result<(), ScanError>) {
+    if is_archive(file_path) {
+        let (mut entries, result) = hash_archive_file(file_path);
+
+        return match result {
+            Ok(()) => (entries, Ok(())),
+            Err(ScanError::Zip(_)) => match compute_file_sha1(file_path) {
+                Ok(hash) => {
+                    entries.push((hash, file_path.to_path_buf(), None));
+                    (entries, Ok(()))
+                }
+                Err(err) => (entries, Err(err)),
+            },
+            Err(err) => (entries, Err(err)),
+        };
     }
 
-    for handle in handles {
-        handle.join().map_err(|_| ScanError::JoinError)?;
+    match compute_whole_file_hash(file_path, fuzzy_matching) {
+        Ok((hash, fuzzy_signature)) => (
+            vec![(hash, file_path.to_path_buf(), fuzzy_signature)],
+            Ok(()),
+        ),
+        Err(err) => (Vec::new(), Err(err)),
     }
+}
+
+/// Hashes every entry in `file_path` as a zip archive (see [`hash_archive_entries`]),
+/// returning whatever entries were hashed before `result` settles — including on
+/// failure, so a budget trip or corrupt entry partway through doesn't discard matches
+/// already found earlier in the archive.
+fn hash_archive_file(
+    file_path: &Path,
+) -> (Vec<(String, PathBuf, Option<String>)>, Result<(), ScanError>) {
+    let file = match File::open(file_path) {
+        Ok(file) => file,
+        Err(err) => return (Vec::new(), Err(err.into())),
+    };
+
+    let mut budget = MAX_DECOMPRESSED_BYTES;
+    let mut out = Vec::new();
+    let result = hash_archive_entries(file, file_path, 0, &mut budget, &mut out);
+    let entries = out
+        .into_iter()
+        .map(|(hash, path)| (hash, path, None))
+        .collect();
+
+    (entries, result)
+}
 
-    Ok(Arc::try_unwrap(hashes).unwrap())
+fn is_archive(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some(ext) if ext.eq_ignore_ascii_case("jar") || ext.eq_ignore_ascii_case("zip")
+    )
+}
+
+/// Streams and hashes every entry in a zip-format archive, reporting each as
+/// `<outer path>!<entry path>`. Recurses into nested archives up to `MAX_ARCHIVE_DEPTH`,
+/// decrementing `budget` as decompressed bytes are read so a zip bomb can't exhaust memory.
+fn hash_archive_entries<R: Read + std::io::Seek>(
+    reader: R,
+    display_prefix: &Path,
+    depth: usize,
+    budget: &mut u64,
+    out: &mut Vec<(String, PathBuf)>,
+) -> Result<(), ScanError> {
+    if depth >= MAX_ARCHIVE_DEPTH {
+        return Ok(());
+    }
+
+    let mut archive = zip::ZipArchive::new(reader)?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let entry_name = entry.name().to_string();
+        let display_path = PathBuf::from(format!("{}!{}", display_prefix.display(), entry_name));
+        let nested = is_archive(Path::new(&entry_name));
+
+        let mut hasher = Sha1::new();
+        let mut nested_bytes = Vec::new();
+        let mut buffer = [0u8; 64 * 1024];
+
+        loop {
+            let bytes_read = entry.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            *budget = budget
+                .checked_sub(bytes_read as u64)
+                .ok_or(ScanError::ArchiveTooLarge)?;
+
+            hasher.update(&buffer[..bytes_read]);
+            if nested {
+                nested_bytes.extend_from_slice(&buffer[..bytes_read]);
+            }
+        }
+
+        out.push((format!("{:x}", hasher.finalize()), display_path));
+
+        if nested {
+            // Ignore unreadable nested archives rather than failing the whole scan.
+            let _ = hash_archive_entries(
+                Cursor::new(nested_bytes),
+                &display_path,
+                depth + 1,
+                budget,
+                out,
+            );
+        }
+    }
+
+    Ok(())
 }
 
 fn compute_file_sha1(file_path: &Path) -> Result<String, ScanError> {
@@ -78,12 +243,36 @@ fn compute_file_sha1(file_path: &Path) -> Result<String, ScanError> {
     Ok(format!("{:x}", hasher.finalize()))
 }
 
-pub fn remove_files(paths: Vec<PathBuf>) -> Result<(), ScanError> {
-    for file in paths {
-        if file.exists() {
-            std::fs::remove_file(file)?;
+/// Computes a whole file's SHA1 hash and, when `fuzzy_matching` is on, its fuzzy
+/// signature, streaming the file once rather than reading it into memory or re-reading
+/// it for each hash.
+fn compute_whole_file_hash(
+    file_path: &Path,
+    fuzzy_matching: bool,
+) -> Result<(String, Option<String>), ScanError> {
+    const BUFFER_SIZE: usize = 1024 * 1024; // 1 MB buffer size
+
+    let mut file = File::open(file_path)?;
+    let mut sha1 = Sha1::new();
+    let mut fuzzy_hasher = if fuzzy_matching {
+        Some(FuzzyHasher::new(file.metadata()?.len()))
+    } else {
+        None
+    };
+    let mut buffer = vec![0; BUFFER_SIZE];
+
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+
+        sha1.update(&buffer[..bytes_read]);
+        if let Some(hasher) = fuzzy_hasher.as_mut() {
+            hasher.update(&buffer[..bytes_read]);
         }
     }
 
-    Ok(())
+    let hash = format!("{:x}", sha1.finalize());
+    Ok((hash, fuzzy_hasher.map(FuzzyHasher::finish)))
 }
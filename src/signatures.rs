@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+/// How long we'll wait on the signature-database request before giving up and falling
+/// back to the cache/embedded list, so a hung DNS lookup or server can't block startup.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default embedded hash list, used when the remote database can't be reached and no
+/// cached copy exists yet. Kept small and deduped; the remote database is the source
+/// of truth for anything new.
+const EMBEDDED_HASHES: &[&str] = &[
+    "179b5da318604f97616b5108f305e2a8e4609484",
+    "1a1c4dcae846866c58cc1abf71fb7f7aa4e7352a",
+    "e4d55310039b965fce6756da5286b481cfb09946",
+    "2f47e57a6bedc729359ffaf6f0149876008b5cc3",
+];
+
+const EMBEDDED_VERSION: &str = "embedded";
+
+/// URL of the Modrinth-hosted signature database. Can be overridden (e.g. in tests or
+/// for self-hosted mirrors) via `SignatureDatabase::fetch_from`.
+pub const DEFAULT_DATABASE_URL: &str = "https://raw.githubusercontent.com/modrinth/oracle/main/signatures.json";
+
+#[derive(Error, Debug)]
+pub enum SignatureError {
+    #[error("IO Error: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("Error parsing signature database: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct SignatureDatabase {
+    pub version: String,
+    pub hashes: Vec<String>,
+    /// Known-bad fuzzy (ssdeep-style) signatures, used for the optional variant-matching
+    /// pass. Absent from older cached databases, so it defaults to empty on deserialize.
+    #[serde(default)]
+    pub fuzzy_hashes: Vec<String>,
+}
+
+impl SignatureDatabase {
+    /// The database bundled with the binary, used when we're offline and no cache exists.
+    pub fn embedded() -> Self {
+        let mut hashes: Vec<String> = EMBEDDED_HASHES.iter().map(|s| s.to_string()).collect();
+        dedup(&mut hashes);
+
+        Self {
+            version: EMBEDDED_VERSION.to_string(),
+            hashes,
+            fuzzy_hashes: Vec::new(),
+        }
+    }
+
+    /// Downloads the latest signature database from `url`, blocking the calling thread.
+    /// Bounded by [`FETCH_TIMEOUT`] so a hung connection can't block forever.
+    pub fn fetch_from(url: &str) -> Result<Self, SignatureError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()?;
+        let response = client.get(url).send()?.error_for_status()?;
+        let mut db: SignatureDatabase = response.json()?;
+        dedup(&mut db.hashes);
+        dedup(&mut db.fuzzy_hashes);
+        Ok(db)
+    }
+
+    pub fn fetch() -> Result<Self, SignatureError> {
+        Self::fetch_from(DEFAULT_DATABASE_URL)
+    }
+
+    fn load_cached(cache_path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(cache_path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save_to_cache(&self, cache_path: &Path) -> Result<(), SignatureError> {
+        if let Some(parent) = cache_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(cache_path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// The cached database if present, otherwise the embedded fallback. Doesn't touch
+    /// the network, so it's safe to call from a UI thread before startup.
+    pub fn load_cached_or_embedded(cache_path: &Path) -> Self {
+        Self::load_cached(cache_path).unwrap_or_else(Self::embedded)
+    }
+
+    /// Fetches the latest database, falling back to the on-disk cache and then the
+    /// embedded list if we're offline. Refreshes the cache on a successful fetch. Blocks
+    /// the calling thread for up to [`FETCH_TIMEOUT`]; callers on a UI thread should use
+    /// [`SignatureDatabase::load_cached_or_embedded`] first and run this in the background.
+    pub fn load_or_fetch(cache_path: &Path) -> Self {
+        match Self::fetch() {
+            Ok(db) => {
+                let _ = db.save_to_cache(cache_path);
+                db
+            }
+            Err(_) => Self::load_cached(cache_path).unwrap_or_else(Self::embedded),
+        }
+    }
+}
+
+/// Removes duplicate entries regardless of position (unlike `Vec::dedup`, which only
+/// collapses adjacent runs).
+fn dedup(values: &mut Vec<String>) {
+    values.sort();
+    values.dedup();
+}
+
+/// Path to the cached signature database, stored next to the eframe app state.
+pub fn cache_path(app_id: &str) -> Option<PathBuf> {
+    eframe::storage_dir(app_id).map(|dir| dir.join("signatures.json"))
+}